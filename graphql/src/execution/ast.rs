@@ -103,25 +103,124 @@ impl SelectionSet {
         item.1.iter()
     }
 
-    /// Append the field for all the sets' types
-    pub fn push(&mut self, new_field: &Field) {
+    /// Project `value`, a resolved value that may carry more fields than
+    /// were actually requested, onto this selection set: the result has
+    /// exactly the requested fields, keyed by their `response_key()` and in
+    /// the order they were selected. Leaf fields are taken as is;
+    /// `interior_fields()` are recursed into using their own
+    /// `selection_set`. A `List` is reshaped element by element against the
+    /// same selection set, and `Null` passes through unchanged.
+    ///
+    /// Fields excluded by `@skip`/`@include` are left out of the result
+    /// even if `prune_skipped` was never called on this selection set, the
+    /// same way `merge_field` already treats them as uncollected
+    pub fn reshape(&self, value: r::Value) -> Result<r::Value, QueryExecutionError> {
+        match value {
+            r::Value::Null => Ok(r::Value::Null),
+            r::Value::List(values) => {
+                let values = values
+                    .into_iter()
+                    .map(|value| self.reshape(value))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(r::Value::List(values))
+            }
+            r::Value::Object(object) => {
+                let fields = self.fields_for_object(&object)?;
+                let mut out = Vec::new();
+                for field in fields.iter().filter(|field| field.is_included()) {
+                    let inner = object
+                        .get(field.name.as_str())
+                        .cloned()
+                        .unwrap_or(r::Value::Null);
+                    let reshaped = if field.is_leaf() {
+                        inner
+                    } else {
+                        field.selection_set.reshape(inner)?
+                    };
+                    out.push((field.response_key().to_string(), reshaped));
+                }
+                Ok(r::Value::Object(out.into_iter().collect()))
+            }
+            value => Ok(value),
+        }
+    }
+
+    /// Pick the field list in `items` that matches `object`'s concrete
+    /// type. If there is only one type in `items`, that one applies
+    /// unconditionally; otherwise the object's `__typename` decides which
+    /// entry to use
+    fn fields_for_object(&self, object: &r::Object) -> Result<&Vec<Field>, QueryExecutionError> {
+        if let [(_, fields)] = self.items.as_slice() {
+            return Ok(fields);
+        }
+        match object.get("__typename") {
+            Some(r::Value::String(typename)) => self
+                .items
+                .iter()
+                .find(|(name, _)| name == typename)
+                .map(|(_, fields)| fields)
+                .ok_or_else(|| QueryExecutionError::AbstractTypeError(typename.to_string())),
+            _ => Err(QueryExecutionError::AbstractTypeError(
+                "missing __typename needed to reshape a value for an abstract type".to_string(),
+            )),
+        }
+    }
+
+    /// Drop all fields that are excluded by a `@skip`/`@include` directive,
+    /// recursing into the surviving fields' nested selection sets. This
+    /// lets callers resolve conditionals once, up front, rather than
+    /// re-checking directives at every step of execution.
+    ///
+    /// `merge_field` already treats excluded fields as uncollected, so a
+    /// legal duplicate that only one fragment excludes merges cleanly
+    /// during `merge`/`push` and the exclusion only needs to be acted on
+    /// once, here, at the end
+    pub fn prune_skipped(self) -> SelectionSet {
+        let items = self
+            .items
+            .into_iter()
+            .map(|(name, fields)| {
+                let fields = fields
+                    .into_iter()
+                    .filter(Field::is_included)
+                    .map(Field::prune_skipped)
+                    .collect();
+                (name, fields)
+            })
+            .collect();
+        SelectionSet { items }
+    }
+
+    /// Append the field for all the sets' types. Fails with
+    /// `QueryExecutionError` if `new_field` can't be merged into a field
+    /// that is already selected under the same response key; callers need
+    /// to propagate that error rather than discard it, since it signals an
+    /// ambiguous selection in the query
+    pub fn push(&mut self, new_field: &Field) -> Result<(), QueryExecutionError> {
         for (_, fields) in &mut self.items {
-            Self::merge_field(fields, new_field.clone());
+            Self::merge_field(fields, new_field.clone())?;
         }
+        Ok(())
     }
 
-    /// Append the fields for all the sets' types
-    pub fn push_fields(&mut self, fields: Vec<&Field>) {
+    /// Append the fields for all the sets' types. See `push` for when this
+    /// fails
+    pub fn push_fields(&mut self, fields: Vec<&Field>) -> Result<(), QueryExecutionError> {
         for field in fields {
-            self.push(field);
+            self.push(field)?;
         }
+        Ok(())
     }
 
     /// Merge `self` with the fields from `other`, which must have the same,
     /// or a subset of, the types of `self`. The `directives` are added to
     /// `self`'s directives so that they take precedence over existing
-    /// directives with the same name
-    pub fn merge(&mut self, other: SelectionSet, directives: Vec<Directive>) {
+    /// directives with the same name. See `push` for when this fails
+    pub fn merge(
+        &mut self,
+        other: SelectionSet,
+        directives: Vec<Directive>,
+    ) -> Result<(), QueryExecutionError> {
         for (other_name, other_fields) in other.items {
             let item = self
                 .items
@@ -130,27 +229,73 @@ impl SelectionSet {
                 .expect("all possible types are already in items");
             for mut other_field in other_fields {
                 other_field.prepend_directives(directives.clone());
-                Self::merge_field(&mut item.1, other_field);
+                Self::merge_field(&mut item.1, other_field)?;
             }
         }
+        Ok(())
     }
 
-    fn merge_field(fields: &mut Vec<Field>, new_field: Field) {
+    /// Merge `new_field` into `fields`, which are all the fields selected
+    /// so far for one concrete object type. A field that `@skip`/`@include`
+    /// excludes is never collected in the first place, so it is neither
+    /// validated for mergeability nor merged: if it shares its response key
+    /// with a field already in `fields` it is discarded right here,
+    /// otherwise it is stored and left for `prune_skipped` to remove later.
+    /// Otherwise, per the GraphQL spec's
+    /// *FieldsInSetCanMerge* rule, two fields with the same response key
+    /// can only be merged if they are guaranteed to produce the same
+    /// result, i.e. they have the same name and the same arguments. Since
+    /// `fields` only ever holds fields for a single concrete type, two
+    /// entries here can always apply to the same runtime object, so the
+    /// relaxation the spec allows for fields on different concrete types
+    /// does not apply
+    fn merge_field(fields: &mut Vec<Field>, new_field: Field) -> Result<(), QueryExecutionError> {
         match fields
             .iter_mut()
             .find(|field| field.response_key() == new_field.response_key())
         {
             Some(field) => {
-                // TODO: check that _field and new_field are mergeable, in
-                // particular that their name, directives and arguments are
-                // compatible
-                field.selection_set.merge(new_field.selection_set, vec![]);
+                if !new_field.is_included() {
+                    // `new_field` would be dropped by `prune_skipped` anyway
+                    // and is discarded here; it was never really part of
+                    // this selection; per
+                    // `CollectFields` it doesn't get compared against, or
+                    // merged into, the fields that did survive
+                } else if !field.is_included() {
+                    // The entry we matched on was itself excluded, so it
+                    // would never have been collected in the first place;
+                    // the live `new_field` takes its place
+                    *field = new_field;
+                } else if field.name != new_field.name
+                    || !arguments_match(&field.arguments, &new_field.arguments)
+                {
+                    return Err(QueryExecutionError::AbstractTypeError(format!(
+                        "field `{}` is selected twice with incompatible names or arguments, \
+                         at {:?} and {:?}",
+                        field.response_key(),
+                        field.position,
+                        new_field.position,
+                    )));
+                } else {
+                    field.selection_set.merge(new_field.selection_set, vec![])?;
+                }
             }
             None => fields.push(new_field),
         }
+        Ok(())
     }
 }
 
+/// Compare two argument lists as sets of `(name, value)` pairs, ignoring
+/// the order in which arguments were given
+fn arguments_match(a: &[(String, r::Value)], b: &[(String, r::Value)]) -> bool {
+    a.len() == b.len()
+        && a.iter().all(|(name, value)| {
+            b.iter()
+                .any(|(other_name, other_value)| name == other_name && value == other_value)
+        })
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Directive {
     pub position: Pos,
@@ -186,12 +331,28 @@ impl Directive {
             _ => false,
         }
     }
+
+    /// Sort `directives` into a stable order: by name, and within the same
+    /// name by their serialized arguments. A field can end up with several
+    /// `@skip`/`@include` applications, one from each fragment it was
+    /// merged from, and those get appended in whatever order the fragments
+    /// happened to be expanded in; sorting first makes comparing two
+    /// directive lists independent of that order
+    pub fn iter_sorted(directives: &[Directive]) -> impl Iterator<Item = &Directive> {
+        let mut sorted: Vec<&Directive> = directives.iter().collect();
+        sorted.sort_by(|a, b| {
+            a.name
+                .cmp(&b.name)
+                .then_with(|| format!("{:?}", a.arguments).cmp(&format!("{:?}", b.arguments)))
+        });
+        sorted.into_iter()
+    }
 }
 
 /// A field to execute as part of a query. When the field is constructed by
 /// `Query::new`, variables are interpolated, and argument values have
 /// already been coerced to the appropriate types for the field argument
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 pub struct Field {
     pub position: Pos,
     pub alias: Option<String>,
@@ -201,6 +362,18 @@ pub struct Field {
     pub selection_set: SelectionSet,
 }
 
+impl PartialEq for Field {
+    fn eq(&self, other: &Self) -> bool {
+        self.position == other.position
+            && self.alias == other.alias
+            && self.name == other.name
+            && self.arguments == other.arguments
+            && self.selection_set == other.selection_set
+            && Directive::iter_sorted(&self.directives)
+                .eq(Directive::iter_sorted(&other.directives))
+    }
+}
+
 impl Field {
     /// Returns the response key of a field, which is either its name or its
     /// alias (if there is one).
@@ -229,6 +402,22 @@ impl Field {
     fn is_leaf(&self) -> bool {
         self.selection_set.is_empty()
     }
+
+    /// Return `true` if this field should be included in the response.
+    /// A field can carry more than one `@skip`/`@include` directive, one
+    /// from each fragment it was merged from, and it is included iff every
+    /// `@include(if: ...)` is true and every `@skip(if: ...)` is false, i.e.
+    /// iff none of its directives individually call for skipping it
+    pub fn is_included(&self) -> bool {
+        !self.directives.iter().any(|directive| directive.skip())
+    }
+
+    /// Recursively prune `@skip`/`@include`-excluded fields from this
+    /// field's nested selection set
+    fn prune_skipped(mut self) -> Field {
+        self.selection_set = self.selection_set.prune_skipped();
+        self
+    }
 }
 
 /// A set of object types, generated from resolving interfaces into the
@@ -241,18 +430,31 @@ pub enum ObjectTypeSet {
 }
 
 impl ObjectTypeSet {
+    /// Convert a type condition into the `ObjectTypeSet` it denotes,
+    /// omitting any type that `visible` rejects. This added a required
+    /// `visible` parameter to what used to be a two-argument function;
+    /// existing callers outside this file need to pass `None` to keep
+    /// their current, unfiltered behavior
     pub fn convert(
         schema: &Schema,
         type_cond: Option<&q::TypeCondition>,
+        visible: Option<&dyn Fn(&str) -> bool>,
     ) -> Result<ObjectTypeSet, QueryExecutionError> {
         match type_cond {
-            Some(q::TypeCondition::On(name)) => Self::from_name(schema, name),
+            Some(q::TypeCondition::On(name)) => Self::from_name(schema, name, visible),
             None => Ok(ObjectTypeSet::Any),
         }
     }
 
-    pub fn from_name(schema: &Schema, name: &str) -> Result<ObjectTypeSet, QueryExecutionError> {
-        let set = resolve_object_types(schema, name)?
+    /// Resolve `name` into the set of concrete object types it stands for,
+    /// omitting any type that `visible` rejects. Pass `None` for `visible`
+    /// when every implementer of an interface or union should be visible
+    pub fn from_name(
+        schema: &Schema,
+        name: &str,
+        visible: Option<&dyn Fn(&str) -> bool>,
+    ) -> Result<ObjectTypeSet, QueryExecutionError> {
+        let set = resolve_object_types(schema, name, visible)?
             .into_iter()
             .map(|ty| ty.name().to_string())
             .collect();
@@ -278,13 +480,15 @@ impl ObjectTypeSet {
     }
 
     /// Return a list of the object type names that are in this type set and
-    /// are also implementations of `current_type`
+    /// are also implementations of `current_type`, omitting any type that
+    /// `visible` rejects
     pub fn type_names(
         &self,
         schema: &Schema,
         current_type: ObjectOrInterface<'_>,
+        visible: Option<&dyn Fn(&str) -> bool>,
     ) -> Result<Vec<String>, QueryExecutionError> {
-        Ok(resolve_object_types(schema, current_type.name())?
+        Ok(resolve_object_types(schema, current_type.name(), visible)?
             .into_iter()
             .map(|obj| obj.name().to_string())
             .filter(|name| match self {
@@ -296,12 +500,17 @@ impl ObjectTypeSet {
 }
 
 /// Look up the type `name` from the schema and resolve interfaces
-/// and unions until we are left with a set of concrete object types
+/// and unions until we are left with a set of concrete object types.
+/// When `visible` is given, any object type for which it returns `false`
+/// is left out of the result, e.g. to mask implementing types that should
+/// not be reachable through interface/union resolution for a given caller
 pub(crate) fn resolve_object_types<'a>(
     schema: &'a Schema,
     name: &str,
+    visible: Option<&dyn Fn(&str) -> bool>,
 ) -> Result<HashSet<ObjectCondition<'a>>, QueryExecutionError> {
     let mut set = HashSet::new();
+    let is_visible = |name: &str| visible.map_or(true, |is_visible| is_visible(name));
     match schema
         .document
         .get_named_type(name)
@@ -309,16 +518,20 @@ pub(crate) fn resolve_object_types<'a>(
     {
         s::TypeDefinition::Interface(intf) => {
             for obj_ty in &schema.types_for_interface()[&EntityType::new(intf.name.to_string())] {
-                set.insert(obj_ty.into());
+                if is_visible(&obj_ty.name) {
+                    set.insert(obj_ty.into());
+                }
             }
         }
         s::TypeDefinition::Union(tys) => {
             for ty in &tys.types {
-                set.extend(resolve_object_types(schema, ty)?)
+                set.extend(resolve_object_types(schema, ty, visible)?)
             }
         }
         s::TypeDefinition::Object(ty) => {
-            set.insert(ty.into());
+            if is_visible(&ty.name) {
+                set.insert(ty.into());
+            }
         }
         s::TypeDefinition::Scalar(_)
         | s::TypeDefinition::Enum(_)
@@ -328,3 +541,375 @@ pub(crate) fn resolve_object_types<'a>(
     }
     Ok(set)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graph::data::subgraph::DeploymentHash;
+
+    const PET_SCHEMA: &str = "
+        interface Pet { name: String! }
+        type Dog implements Pet { name: String! }
+        type Cat implements Pet { name: String! }
+        type Query { pets: [Pet!]! }
+    ";
+
+    fn pet_schema() -> Schema {
+        let id = DeploymentHash::new("test").expect("a valid deployment hash");
+        Schema::parse(PET_SCHEMA, id).expect("the test schema parses")
+    }
+
+    fn pos() -> Pos {
+        Pos { line: 0, column: 0 }
+    }
+
+    fn directive(name: &str, if_value: Option<bool>) -> Directive {
+        let arguments = if_value
+            .map(|b| vec![("if".to_string(), r::Value::Boolean(b))])
+            .unwrap_or_default();
+        Directive {
+            position: pos(),
+            name: name.to_string(),
+            arguments,
+        }
+    }
+
+    fn field(
+        name: &str,
+        alias: Option<&str>,
+        arguments: Vec<(String, r::Value)>,
+        directives: Vec<Directive>,
+        selection_set: SelectionSet,
+    ) -> Field {
+        Field {
+            position: pos(),
+            alias: alias.map(|s| s.to_string()),
+            name: name.to_string(),
+            arguments,
+            directives,
+            selection_set,
+        }
+    }
+
+    fn leaf(name: &str, directives: Vec<Directive>) -> Field {
+        field(name, None, vec![], directives, SelectionSet::new(vec![]))
+    }
+
+    fn obj(fields: Vec<(&str, r::Value)>) -> r::Value {
+        r::Value::Object(
+            fields
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn merge_field_rejects_incompatible_duplicates() {
+        let mut set = SelectionSet::new(vec!["Pet".to_string()]);
+        let short = field(
+            "name",
+            None,
+            vec![("format".to_string(), r::Value::String("short".to_string()))],
+            vec![],
+            SelectionSet::new(vec![]),
+        );
+        let long = field(
+            "name",
+            None,
+            vec![("format".to_string(), r::Value::String("long".to_string()))],
+            vec![],
+            SelectionSet::new(vec![]),
+        );
+
+        set.push(&short).unwrap();
+        let err = set.push(&long).unwrap_err();
+        assert!(matches!(err, QueryExecutionError::AbstractTypeError(..)));
+    }
+
+    #[test]
+    fn merge_field_allows_excluded_then_bare_duplicate() {
+        let mut set = SelectionSet::new(vec!["Pet".to_string()]);
+        let skipped = leaf("name", vec![directive("skip", Some(true))]);
+        let bare = leaf("name", vec![]);
+
+        set.push(&skipped).unwrap();
+        set.push(&bare).unwrap();
+
+        let merged = set.single_field().expect("exactly one field survives");
+        assert!(merged.is_included());
+    }
+
+    #[test]
+    fn merge_field_allows_bare_then_excluded_duplicate() {
+        let mut set = SelectionSet::new(vec!["Pet".to_string()]);
+        let bare = leaf("name", vec![]);
+        let skipped = leaf("name", vec![directive("skip", Some(true))]);
+
+        set.push(&bare).unwrap();
+        set.push(&skipped).unwrap();
+
+        let merged = set.single_field().expect("exactly one field survives");
+        assert!(merged.is_included());
+    }
+
+    #[test]
+    fn is_included_folds_all_skip_and_include_directives() {
+        let included = leaf(
+            "name",
+            vec![
+                directive("include", Some(true)),
+                directive("skip", Some(false)),
+            ],
+        );
+        assert!(included.is_included());
+
+        let excluded_by_skip = leaf(
+            "name",
+            vec![
+                directive("include", Some(true)),
+                directive("skip", Some(true)),
+            ],
+        );
+        assert!(!excluded_by_skip.is_included());
+
+        let excluded_by_include = leaf("name", vec![directive("include", Some(false))]);
+        assert!(!excluded_by_include.is_included());
+    }
+
+    #[test]
+    fn field_equality_is_directive_order_independent() {
+        let a = leaf(
+            "name",
+            vec![
+                directive("include", Some(true)),
+                directive("skip", Some(false)),
+            ],
+        );
+        let b = leaf(
+            "name",
+            vec![
+                directive("skip", Some(false)),
+                directive("include", Some(true)),
+            ],
+        );
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn merge_field_merges_duplicates_whose_directives_arrive_in_different_order() {
+        let mut set = SelectionSet::new(vec!["Pet".to_string()]);
+        let a = field(
+            "name",
+            None,
+            vec![],
+            vec![
+                directive("include", Some(true)),
+                directive("skip", Some(false)),
+            ],
+            SelectionSet::new(vec![]),
+        );
+        let b = field(
+            "name",
+            None,
+            vec![],
+            vec![
+                directive("skip", Some(false)),
+                directive("include", Some(true)),
+            ],
+            SelectionSet::new(vec![]),
+        );
+
+        set.push(&a).unwrap();
+        set.push(&b).unwrap();
+
+        let merged = set.single_field().expect("exactly one field survives");
+        assert!(merged.is_included());
+    }
+
+    #[test]
+    fn prune_skipped_removes_excluded_fields_recursively() {
+        let mut pet_fields = SelectionSet::new(vec!["Pet".to_string()]);
+        pet_fields.push(&leaf("name", vec![])).unwrap();
+        pet_fields
+            .push(&leaf("age", vec![directive("skip", Some(true))]))
+            .unwrap();
+
+        let mut query = SelectionSet::new(vec!["Query".to_string()]);
+        query
+            .push(&field("pet", None, vec![], vec![], pet_fields))
+            .unwrap();
+
+        let pruned = query.prune_skipped();
+        let pet_field = pruned.single_field().expect("the `pet` field survives");
+        let remaining: Vec<&str> = pet_field
+            .selection_set
+            .fields()
+            .flat_map(|(_, fields)| fields)
+            .map(|field| field.name.as_str())
+            .collect();
+        assert_eq!(remaining, vec!["name"]);
+    }
+
+    #[test]
+    fn reshape_respects_aliases_and_query_order() {
+        let mut set = SelectionSet::new(vec!["Pet".to_string()]);
+        set.push(&field(
+            "name",
+            Some("n"),
+            vec![],
+            vec![],
+            SelectionSet::new(vec![]),
+        ))
+        .unwrap();
+        set.push(&leaf("age", vec![])).unwrap();
+
+        let value = obj(vec![
+            ("age", r::Value::Int(3)),
+            ("name", r::Value::String("Mocha".to_string())),
+        ]);
+
+        let reshaped = set.reshape(value).unwrap();
+        match reshaped {
+            r::Value::Object(object) => {
+                let keys: Vec<&str> = object.iter().map(|(name, _)| name.as_str()).collect();
+                assert_eq!(keys, vec!["n", "age"]);
+                assert_eq!(
+                    object.get("n"),
+                    Some(&r::Value::String("Mocha".to_string()))
+                );
+            }
+            other => panic!("expected an object, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn reshape_recurses_into_lists() {
+        let mut pet_fields = SelectionSet::new(vec!["Pet".to_string()]);
+        pet_fields.push(&leaf("name", vec![])).unwrap();
+
+        let mut query = SelectionSet::new(vec!["Query".to_string()]);
+        query
+            .push(&field("pets", None, vec![], vec![], pet_fields))
+            .unwrap();
+
+        let value = obj(vec![(
+            "pets",
+            r::Value::List(vec![
+                obj(vec![("name", r::Value::String("Mocha".to_string()))]),
+                obj(vec![("name", r::Value::String("Jasper".to_string()))]),
+            ]),
+        )]);
+
+        let reshaped = query.reshape(value).unwrap();
+        let pets = match reshaped {
+            r::Value::Object(object) => object.get("pets").cloned().unwrap(),
+            other => panic!("expected an object, got {:?}", other),
+        };
+        match pets {
+            r::Value::List(pets) => {
+                let names: Vec<r::Value> = pets
+                    .into_iter()
+                    .map(|pet| match pet {
+                        r::Value::Object(object) => object.get("name").cloned().unwrap(),
+                        other => panic!("expected an object, got {:?}", other),
+                    })
+                    .collect();
+                assert_eq!(
+                    names,
+                    vec![
+                        r::Value::String("Mocha".to_string()),
+                        r::Value::String("Jasper".to_string())
+                    ]
+                );
+            }
+            other => panic!("expected a list, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn reshape_dispatches_fields_by_typename() {
+        let set = SelectionSet {
+            items: vec![
+                ("Dog".to_string(), vec![leaf("bark", vec![])]),
+                ("Cat".to_string(), vec![leaf("meow", vec![])]),
+            ],
+        };
+
+        let value = obj(vec![
+            ("__typename", r::Value::String("Cat".to_string())),
+            ("meow", r::Value::String("meow!".to_string())),
+            ("bark", r::Value::String("should not appear".to_string())),
+        ]);
+
+        let reshaped = set.reshape(value).unwrap();
+        match reshaped {
+            r::Value::Object(object) => {
+                assert_eq!(
+                    object.get("meow"),
+                    Some(&r::Value::String("meow!".to_string()))
+                );
+                assert_eq!(object.get("bark"), None);
+            }
+            other => panic!("expected an object, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn reshape_drops_excluded_fields_without_prune_skipped() {
+        let mut set = SelectionSet::new(vec!["Pet".to_string()]);
+        set.push(&leaf("name", vec![])).unwrap();
+        set.push(&leaf("age", vec![directive("skip", Some(true))]))
+            .unwrap();
+
+        let value = obj(vec![
+            ("name", r::Value::String("Mocha".to_string())),
+            ("age", r::Value::Int(3)),
+        ]);
+
+        let reshaped = set.reshape(value).unwrap();
+        match reshaped {
+            r::Value::Object(object) => {
+                assert_eq!(
+                    object.get("name"),
+                    Some(&r::Value::String("Mocha".to_string()))
+                );
+                assert_eq!(object.get("age"), None);
+            }
+            other => panic!("expected an object, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn resolve_object_types_honors_visibility_filter() {
+        let schema = pet_schema();
+
+        let all = resolve_object_types(&schema, "Pet", None).unwrap();
+        let all_names: HashSet<String> = all.iter().map(|ty| ty.name().to_string()).collect();
+        assert_eq!(
+            all_names,
+            vec!["Dog".to_string(), "Cat".to_string()].into_iter().collect()
+        );
+
+        let hide_cat: &dyn Fn(&str) -> bool = &|name| name != "Cat";
+        let visible = resolve_object_types(&schema, "Pet", Some(hide_cat)).unwrap();
+        let visible_names: HashSet<String> =
+            visible.iter().map(|ty| ty.name().to_string()).collect();
+        assert_eq!(visible_names, vec!["Dog".to_string()].into_iter().collect());
+    }
+
+    #[test]
+    fn object_type_set_from_name_excludes_hidden_types() {
+        let schema = pet_schema();
+        let hide_cat: &dyn Fn(&str) -> bool = &|name| name != "Cat";
+
+        let set = ObjectTypeSet::from_name(&schema, "Pet", Some(hide_cat)).unwrap();
+        match set {
+            ObjectTypeSet::Only(names) => {
+                assert!(names.contains("Dog"));
+                assert!(!names.contains("Cat"));
+            }
+            ObjectTypeSet::Any => panic!("expected a concrete set of types"),
+        }
+    }
+}